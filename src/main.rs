@@ -1,4 +1,8 @@
 use clap::{command, Parser};
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -15,34 +19,248 @@ struct Cli {
     #[arg(short, long, default_value_t = false)]
     restart: bool,
 
-    #[arg(value_name = "COMMAND", allow_hyphen_values = true, required = true)]
+    #[arg(short, long, value_name = "SIGNAL", default_value = "SIGTERM")]
+    signal: String,
+
+    #[arg(long, value_name = "SECONDS", default_value_t = 5)]
+    kill_grace: u64,
+
+    #[arg(short, long, default_value_t = false)]
+    group: bool,
+
+    #[arg(long, value_name = "FILE")]
+    log: Option<std::path::PathBuf>,
+
+    // Additional commands to supervise alongside the positional one; may be
+    // repeated. Each value is one command line, split into the program and its
+    // arguments with quotes honoured. Every command gets its own timeout/restart.
+    #[arg(long, value_name = "COMMAND")]
+    exec: Vec<String>,
+
+    // When any supervised command exits without being restarted, signal the
+    // others to shut down too instead of leaving them running.
+    #[arg(long, default_value_t = false)]
+    all_or_nothing: bool,
+
+    #[arg(value_name = "COMMAND", allow_hyphen_values = true)]
     command: Vec<String>,
 }
 
-// Messages between threads
+// Translate a signal name (with or without the SIG prefix) or a raw number
+// into the numeric value accepted by libc::kill. Panics on an unknown name so
+// a typo in --signal is reported immediately rather than silently ignored.
+#[cfg(unix)]
+fn parse_signal(name: &str) -> i32 {
+    let upper = name.to_uppercase();
+    let bare = upper.strip_prefix("SIG").unwrap_or(&upper);
+    match bare {
+        "TERM" => libc::SIGTERM,
+        "KILL" => libc::SIGKILL,
+        "INT" => libc::SIGINT,
+        "HUP" => libc::SIGHUP,
+        "QUIT" => libc::SIGQUIT,
+        "USR1" => libc::SIGUSR1,
+        "USR2" => libc::SIGUSR2,
+        other => other
+            .parse::<i32>()
+            .unwrap_or_else(|_| panic!("Unknown signal: {}", name)),
+    }
+}
+
+// Deliver a signal to a target. A positive target is a single pid; a negative
+// target is a process group (as accepted by kill(2)), used by --group mode to
+// signal the whole subtree at once. On Windows there is no signal concept so
+// the caller falls back to Child::kill instead and this is never reached.
+#[cfg(unix)]
+fn send_signal(target: i32, signal: i32) {
+    // SAFETY: kill() with a target we own and a valid signal number has no
+    // memory safety implications; a failure (e.g. the process already exited)
+    // is benign and handled by the subsequent try_wait loop.
+    unsafe {
+        libc::kill(target as libc::pid_t, signal);
+    }
+}
+
+// Block until the child with this pid exits, without reaping it. waitid with
+// WNOWAIT leaves the zombie in place so the command thread can still collect the
+// real ExitStatus via Child::try_wait; this lets a reaper thread notify the
+// supervisor of an exit the instant it happens instead of polling.
+#[cfg(unix)]
+fn wait_for_exit(pid: u32) {
+    loop {
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        // SAFETY: waitid only writes the siginfo_t we pass and observes a child
+        // we own; WNOWAIT means it has no side effect beyond blocking.
+        let r = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if r == 0 {
+            return;
+        }
+        // Retry if interrupted by a signal; otherwise the child is already gone
+        // (or unknown) and the command thread's try_wait will sort it out.
+        if std::io::Error::last_os_error().raw_os_error() == Some(libc::EINTR) {
+            continue;
+        }
+        return;
+    }
+}
+
+// Block until the process handle is signalled (the child exited). Like the Unix
+// path this does not reap: Child::try_wait still returns the status afterwards.
+#[cfg(windows)]
+fn wait_for_exit(handle: usize) {
+    const INFINITE: u32 = 0xFFFF_FFFF;
+    extern "system" {
+        fn WaitForSingleObject(handle: *mut std::ffi::c_void, milliseconds: u32) -> u32;
+    }
+    // SAFETY: the handle comes from a live Child we keep alive for the duration
+    // of the wait, and WaitForSingleObject only reads it.
+    unsafe {
+        WaitForSingleObject(handle as *mut std::ffi::c_void, INFINITE);
+    }
+}
+
+// Deadline for a tear-down wait: the kill grace the child is given to honour the
+// first signal, plus a small margin for the SIGKILL escalation and reap. Waiting
+// this long lets a child that ignores SIGTERM still be SIGKILLed before rekill
+// exits, while bounding the wait so a wedged command thread cannot hang rekill.
+fn shutdown_bound(kill_grace: u64) -> std::time::Instant {
+    std::time::Instant::now()
+        + std::time::Duration::from_secs(kill_grace)
+        + std::time::Duration::from_millis(500)
+}
+
+// Split an --exec command line into its program and arguments. A plain
+// whitespace split mangles quoted arguments (e.g. --exec "sh -c 'sleep 1'"),
+// so this honours single and double quotes and a backslash escape. It is a
+// minimal shell-style splitter, not a full parser, but it covers the common
+// cases without pulling in another crate.
+fn split_command(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut has_token = false;
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if !in_single => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                    has_token = true;
+                }
+            }
+            '\'' if !in_double => {
+                in_single = !in_single;
+                has_token = true;
+            }
+            '"' if !in_single => {
+                in_double = !in_double;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_single && !in_double => {
+                if has_token {
+                    args.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        args.push(current);
+    }
+    args
+}
+
+// Which of the child's output streams a captured line came from
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+// Messages between threads. Messages flowing back to main travel on a single
+// shared channel as (command id, Message) pairs so one main loop can multiplex
+// over several supervised commands; the id indexes into main's per-command
+// table. Messages main sends out (Start/Kill, the timer controls) go on each
+// command's own channel, so they need no id.
 enum Message {
     // Command thread to main
     Endpoint(std::sync::mpsc::Sender<Message>),
     Finished, // Command died before timeout
-    Running,  // Command still running
     Killed,   // Confirm that the command was killed as requested
+    Output { stream: Stream, line: String }, // A line read from the child's stdout/stderr
+    // Reaper thread to command thread
+    Reaped, // The child exited; the command thread collects its status
     // Main to command thread
     Start, // Start the command process
-    Poll, // Check if the command is still running
     Kill, // Kill the command process
     // Restart is KillCommand + StartCommand
     // Ctrl-C handler to main
     CtrlC,
+    // Timer thread to main
+    Timeout, // The countdown elapsed; the command must be killed
+    // Main to timer thread
+    ResetTimer,  // Restart the countdown from zero (sent on every Start)
+    PauseTimer,  // Freeze the countdown, remembering the remaining duration
+    ResumeTimer, // Resume the countdown from the remembered remaining duration
+    // Signal handler to main (SIGUSR1 / SIGUSR2)
+    Pause,  // Request the timer to be paused
+    Resume, // Request the timer to be resumed
 }
 
 fn main() {
     let cli = Cli::parse();
-    let command = cli.command;
     let verbose = cli.verbose;
     let quiet = cli.quiet;
     let time = cli.time;
     let restart = cli.restart;
-    const POLL_TIME: u64 = 500; // milliseconds
+    let kill_grace = cli.kill_grace;
+    let group = cli.group;
+    let all_or_nothing = cli.all_or_nothing;
+    // Collect the commands to supervise: the positional command (if any) plus
+    // each --exec command line split into program and arguments. At least one
+    // is required.
+    let mut commands: Vec<Vec<String>> = Vec::new();
+    if !cli.command.is_empty() {
+        commands.push(cli.command);
+    }
+    for exec in &cli.exec {
+        let argv = split_command(exec);
+        if !argv.is_empty() {
+            commands.push(argv);
+        }
+    }
+    if commands.is_empty() {
+        eprintln!("No command given: pass a COMMAND or at least one --exec");
+        std::process::exit(2);
+    }
+    // Optional log file that mirrors captured child output; appended to so a
+    // restarted or long-running supervised job keeps a single continuous log.
+    let mut log_file = cli.log.as_ref().map(|path| {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .expect("Failed to open log file")
+    });
+    // Wall-clock origin used to stamp forwarded output lines with elapsed time.
+    let program_start = std::time::Instant::now();
+    #[cfg(unix)]
+    let kill_signal = parse_signal(&cli.signal);
+    // On Windows there are no Unix signals; the command thread falls back to
+    // an unconditional kill() and this value is never consulted.
+    #[cfg(not(unix))]
+    let kill_signal: i32 = 0;
 
     // Messages for the user
     // (Note: macros must be declared here where variables verbose and quiet are in scope)
@@ -89,29 +307,109 @@ fn main() {
         };
     }
 
-    verbose!("Command: {:?}", command);
+    verbose!("Commands: {:?}", commands);
     verbose!("Verbose: {}", verbose);
     verbose!("Time: {} seconds", time);
     verbose!("Restart: {}", restart);
-    verbose!("Poll time: {} milliseconds", POLL_TIME);
+    verbose!("Signal: {}", cli.signal);
+    verbose!("Kill grace: {} seconds", kill_grace);
+    verbose!("Group: {}", group);
+    verbose!("All or nothing: {}", all_or_nothing);
 
-    let (sender, thread_to_main_receiver) = std::sync::mpsc::channel::<Message>();
-    let thread_to_main_sender = sender.clone();
+    // Every command and timer thread reports back on this one channel, tagging
+    // each message with its command id so main can tell them apart.
+    let (sender, thread_to_main_receiver) = std::sync::mpsc::channel::<(usize, Message)>();
     let ctrlc_to_main_sender = sender.clone();
 
-    // Start the command thread that controls the process that executes the user command
-    let _command_thread = std::thread::Builder::new()
-        .name("command".to_string()) // This is shown in panic! messages
-        .spawn(move || {
-            debug!("The command thread started");
-            // Function command_thread is a loop that does not return
-            command_thread(command, verbose, thread_to_main_sender);
-            std::unreachable!();
+    // Per-command supervision state, indexed by command id. `to_thread` is filled
+    // in once the command thread reports its endpoint; `to_timer` drives that
+    // command's own timeout countdown; `done` records that it finished without
+    // being restarted.
+    struct Supervised {
+        to_thread: Option<std::sync::mpsc::Sender<Message>>,
+        to_timer: std::sync::mpsc::Sender<Message>,
+        label: String,
+        done: bool,
+    }
+    let mut supervised: Vec<Supervised> = Vec::with_capacity(commands.len());
+
+    // Spawn one command thread and one timer thread per command. Each timer owns
+    // an independent countdown (chunk0-4) so the commands time out separately.
+    for (id, command) in commands.into_iter().enumerate() {
+        let label = command[0].clone();
+        let thread_to_main_sender = sender.clone();
+        let _command_thread = std::thread::Builder::new()
+            .name(format!("command-{}", id)) // This is shown in panic! messages
+            .spawn(move || {
+                // Function command_thread is a loop that does not return
+                command_thread(
+                    id,
+                    command,
+                    verbose,
+                    kill_signal,
+                    kill_grace,
+                    group,
+                    thread_to_main_sender,
+                );
+                std::unreachable!();
+            });
+
+        // The timer thread owns the timeout countdown. Main drives it with
+        // ResetTimer/PauseTimer/ResumeTimer and receives Message::Timeout when the
+        // deadline elapses. Owning the countdown in one place fixes the restart bug
+        // (the timeout is reset on every Start) and makes pause/resume possible.
+        let (timer_sender, timer_receiver) = std::sync::mpsc::channel::<Message>();
+        let timer_to_main_sender = sender.clone();
+        let _timer_thread = std::thread::Builder::new()
+            .name(format!("timer-{}", id))
+            .spawn(move || {
+                timer_thread(id, time, verbose, timer_receiver, timer_to_main_sender);
+            });
+
+        supervised.push(Supervised {
+            to_thread: None,
+            to_timer: timer_sender,
+            label,
+            done: false,
         });
+    }
 
-    // The channel from main to command thread will be established when the command thread starts
-    // and sends the endpoint using message CommandSender
-    let mut main_to_thread_sender: Option<std::sync::mpsc::Sender<Message>> = None;
+    // Set once any command triggers an --all-or-nothing shutdown (or Ctrl-C), so
+    // the terminal-event handlers stop restarting commands and instead count
+    // every command down before exiting. `shutdown_deadline` bounds that wait by
+    // the kill grace (plus a small margin for the SIGKILL escalation and reap) so
+    // a wedged command thread cannot make rekill hang forever.
+    let mut shutting_down = false;
+    let mut shutdown_deadline: Option<std::time::Instant> = None;
+
+    // SIGUSR1 pauses the countdowns and SIGUSR2 resumes them, so long-running
+    // supervised jobs can be frozen without being killed. The signals are turned
+    // into Pause/Resume messages that main forwards into every timer thread.
+    #[cfg(unix)]
+    {
+        let signal_to_main_sender = sender.clone();
+        if let Ok(mut signals) = signal_hook::iterator::Signals::new([
+            signal_hook::consts::SIGUSR1,
+            signal_hook::consts::SIGUSR2,
+        ]) {
+            let _ = std::thread::Builder::new()
+                .name("signals".to_string())
+                .spawn(move || {
+                    for sig in signals.forever() {
+                        let message = if sig == signal_hook::consts::SIGUSR1 {
+                            Message::Pause
+                        } else {
+                            Message::Resume
+                        };
+                        // Pause/Resume are global; main fans them out to every
+                        // timer, so the id here is just a placeholder.
+                        if signal_to_main_sender.send((0, message)).is_err() {
+                            break;
+                        }
+                    }
+                });
+        }
+    }
 
     // Ctrl-C handler
     let _ = ctrlc::set_handler(move || {
@@ -119,144 +417,214 @@ fn main() {
         debug!("Send CtrlC message to the main thread");
         // Send CtrlC message to the main thread
         ctrlc_to_main_sender
-            .send(Message::CtrlC)
+            .send((0, Message::CtrlC))
             .expect("Failed to send CtrlC message");
     });
 
-    // Set initial timeout time
-    let mut timeout = std::time::Instant::now() + std::time::Duration::from_secs(time);
-
     // The main thread's message receiver loop
     loop {
-        match thread_to_main_receiver
-            .recv_timeout(std::time::Duration::from_millis(POLL_TIME))
-            // .expect("Failed to receive message from thread to main channel")
-        {
-            // The command thread starts and sends main_to_thread_sender endpoint
-            // with message CommandSender
-            Ok(Message::Endpoint(sender)) => {
-                main_to_thread_sender = Some(sender.clone());
+        let (id, message) = if let Some(deadline) = shutdown_deadline {
+            // While winding down, wait for tear-down acknowledgements but never
+            // past the bounded deadline.
+            let wait = deadline.saturating_duration_since(std::time::Instant::now());
+            match thread_to_main_receiver.recv_timeout(wait) {
+                Ok(tagged) => tagged,
+                Err(_) => {
+                    // Deadline elapsed (or the channel is gone); stop waiting.
+                    verbose!("Shutdown wait elapsed; exiting");
+                    std::process::exit(0);
+                }
+            }
+        } else {
+            match thread_to_main_receiver.recv() {
+                Ok(tagged) => tagged,
+                // The command and timer threads are gone (main is exiting or a
+                // thread panicked); nothing more will arrive on this channel.
+                Err(_) => {
+                    debug!("Main: Message channel disconnected, exiting");
+                    std::process::exit(0);
+                }
+            }
+        };
+        match message {
+            // A command thread started and sent the endpoint for its own channel.
+            // Record it, then start that command and reset its countdown.
+            Message::Endpoint(endpoint) => {
                 debug!("Main: Endpoint message received");
-                // Main sends StartCommand message to the command thread
-                if let Some(t) = &main_to_thread_sender {
-                    t.send(Message::Start)
-                        .expect("Failed to send start command message");
-                    debug!("Main: Sent Start message")
+                supervised[id].to_thread = Some(endpoint);
+                let s = &supervised[id];
+                s.to_thread
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::Start)
+                    .expect("Failed to send start command message");
+                s.to_timer
+                    .send(Message::ResetTimer)
+                    .expect("Failed to send reset timer message");
+                debug!("Main: Sent Start message")
+            }
+
+            // Timer thread reports that a command's countdown elapsed: kill it.
+            // The Killed handler restarts it, which resets the timer via Start.
+            Message::Timeout => {
+                debug!("Main: Timeout message received");
+                info!("Timeout reached: {}", supervised[id].label);
+                if let Some(t) = &supervised[id].to_thread {
+                    t.send(Message::Kill)
+                        .expect("Failed to send kill command message");
+                    debug!("Main: Sent Kill message");
                 } else {
-                    panic!("Command sender received but no channel established");
+                    panic!("Timeout message received but no channel established");
                 }
             }
 
-
-            // Main thread gets a poll message from the poll thread
-            // Poll message is sent to the command thread to check
-            // if the command is still running
-            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
-                debug!("Main: Message loop timeout");
-                // Check if timeout is reached
-                if std::time::Instant::now() > timeout {
-                    // Timeout reached
-                    info!("Timeout reached");
-                    // Main sends Kill message
-                    if let Some(t) = &main_to_thread_sender {
-                        t.send(Message::Kill)
-                            .expect("Failed to send kill command message");
-                        debug!("Main: Sent Kill message");
-                    } else {
-                        panic!("Timeout message received but no channel established");
-                    }
-                    // Start message will be sent by Killed message handler
-                    // Set new timeout time
-                    timeout = std::time::Instant::now() + std::time::Duration::from_secs(time);
-                } else {
-                    // Timeout not reached
-                    // Main sends Poll message to the command thread to check if the command is alive
-                    if let Some(t) = &main_to_thread_sender {
-                        t.send(Message::Poll)
-                            .expect("Failed to send poll message");
-                        debug!("Main: Sent Poll message")
-                    } else {
-                        debug!("Main: Trying to send Poll message before the channel was established");
-                    }
+            // SIGUSR1/SIGUSR2 pause/resume every command's countdown at once.
+            Message::Pause => {
+                debug!("Main: Pause message received");
+                info!("Pausing timeout");
+                for s in &supervised {
+                    s.to_timer
+                        .send(Message::PauseTimer)
+                        .expect("Failed to send pause timer message");
+                }
+            }
+            Message::Resume => {
+                debug!("Main: Resume message received");
+                info!("Resuming timeout");
+                for s in &supervised {
+                    s.to_timer
+                        .send(Message::ResumeTimer)
+                        .expect("Failed to send resume timer message");
                 }
             }
 
-            // Main thread gets a command finished message from the command thread
-            // This is a respond to PollCommand message
-            // This tells that the command process finished before the timeout
-            Ok(Message::Finished) => {
+            // A reaper thread detected a command's child exit before its timeout.
+            Message::Finished => {
                 debug!("Main: Command finished message received");
+                // While winding down a command exiting just marks it down; once
+                // every command is down we exit.
+                if shutting_down {
+                    supervised[id].done = true;
+                    if supervised.iter().all(|s| s.done) {
+                        verbose!("Exiting...");
+                        std::process::exit(0);
+                    }
+                    continue;
+                }
                 if restart {
-                    // BUG: Here is a bug! The timeout timer must start from 
-                    // the zero again when the command is restarted
-                    // This requires that the timeout thread is either restarted
-                    // or it receives a message to reset the timer
-                    // It requires a whone new channel
-                    info!("Command finished before timeout. Restarting.");
-                    // Main sends Start message
-                    if let Some(t) = &main_to_thread_sender {
+                    info!("Command finished before timeout. Restarting: {}", supervised[id].label);
+                    // Main sends Start message and resets the countdown so the
+                    // restarted command gets a full timeout window (see the timer
+                    // thread) rather than inheriting the nearly-elapsed deadline.
+                    let s = &supervised[id];
+                    if let Some(t) = &s.to_thread {
                         t.send(Message::Start)
                             .expect("Failed to send start command message");
+                        s.to_timer
+                            .send(Message::ResetTimer)
+                            .expect("Failed to send reset timer message");
                         debug!("Main: Sent Start message");
                     } else {
                         panic!("Command finished message received but no channel established");
                     }
-                    continue;
                 } else {
-                    verbose!("Exiting...");
-                    info!("Command finished before restart timeout, if you want to restart it, add --restart");
-                    // Subthreads are killed when the main thread exits
-                    // TODO: return code could be the return code of the command process
-                    std::process::exit(0);
+                    info!("Command finished before restart timeout, if you want to restart it, add --restart: {}", supervised[id].label);
+                    supervised[id].done = true;
+                    // In --all-or-nothing mode one command finishing tears the
+                    // whole group down; otherwise keep the others running.
+                    if all_or_nothing {
+                        shutting_down = true;
+                        shutdown_deadline = Some(shutdown_bound(kill_grace));
+                        verbose!("Shutting the remaining commands down (--all-or-nothing)");
+                        for s in &supervised {
+                            if !s.done {
+                                if let Some(t) = &s.to_thread {
+                                    let _ = t.send(Message::Kill);
+                                }
+                            }
+                        }
+                    }
+                    // Exit once every command is down (covers the lone command and
+                    // the last of a group, whether or not --all-or-nothing is set).
+                    if supervised.iter().all(|s| s.done) {
+                        verbose!("Exiting...");
+                        // Subthreads are killed when the main thread exits
+                        // TODO: return code could be the return code of the command process
+                        std::process::exit(0);
+                    }
                 }
             }
 
             // Main thread gets a CtrlC message from the Ctrl-C handler
-            Ok(Message::CtrlC) => {
+            Message::CtrlC => {
                 debug!("Main: CtrlC message received");
-                debug!("Main: Sending KillCommand message to the command thread");
-                // Main sends Kill message
-                if let Some(t) = &main_to_thread_sender {
-                    t.send(Message::Kill)
-                        .expect("Failed to send kill command message (ctrl-C)");
-                    debug!("Main: Sent Kill message");
-                } else {
-                    // Ctrl-C may happen before the command thread channel is established
-                    debug!("Main: CtrlC message received but no channel established");
+                debug!("Main: Sending Kill message to every command thread");
+                shutting_down = true;
+                shutdown_deadline = Some(shutdown_bound(kill_grace));
+                for s in &supervised {
+                    if !s.done {
+                        if let Some(t) = &s.to_thread {
+                            let _ = t.send(Message::Kill);
+                        }
+                    }
                 }
-                // Exit must wait for the command to be killed but we should duplicate the kill command
-                // to kill command and die. Therefore, we just simply give the command thread and process
-                // 0.1 seconds to clean up and then main thread exits and kills all subthreads as well
-                std::thread::sleep(std::time::Duration::from_millis(100));
-                std::process::exit(0);
-            }
-
-            // Main thread get a command running message from the command thread
-            // This is sent as a result to PollCommand message
-            // when the command is still running OK
-            // This is used just to notify the user if requested
-            // (Command thread does not give verbose output to the user directly)
-            Ok(Message::Running) => {
-                debug!("Main: Running message received");
-                verbose!("Command still running OK");
-            }
-
-            // Main thread gets a command killed message from the command thread
-            // This is confirmation that the command process was killed
-            // as a result to KillCommand message
-            Ok(Message::Killed) => {
+                // Do not exit yet: the kill handler waits up to --kill-grace before
+                // escalating to SIGKILL, so we wait for the Killed acknowledgements
+                // (bounded by shutdown_deadline) instead of racing the child out
+                // the door and leaking a process that ignores the first signal.
+                if supervised.iter().all(|s| s.done) {
+                    std::process::exit(0);
+                }
+            }
+
+            // A command was killed (timeout or shutdown). Restart it for a new
+            // life unless we are tearing the whole group down.
+            Message::Killed => {
                 debug!("Main: Killed message received");
                 verbose!("Command killed because of the timeout");
-                // Main sends Start message
-                if let Some(t) = &main_to_thread_sender {
+                if shutting_down {
+                    debug!("Main: Not restarting during shutdown");
+                    supervised[id].done = true;
+                    if supervised.iter().all(|s| s.done) {
+                        verbose!("Exiting...");
+                        std::process::exit(0);
+                    }
+                    continue;
+                }
+                let s = &supervised[id];
+                if let Some(t) = &s.to_thread {
                     t.send(Message::Start)
                         .expect("Failed to send start command message");
+                    s.to_timer
+                        .send(Message::ResetTimer)
+                        .expect("Failed to send reset timer message");
                     debug!("Main: Sent Start message");
                 } else {
                     panic!("Command killed message received but no channel established");
                 }
             }
 
+            // A line captured from a command's stdout or stderr. Lines are
+            // forwarded to the console tagged with the command label, the stream
+            // and the elapsed time since startup, and appended to --log if given.
+            Message::Output { stream, line } => {
+                let elapsed = program_start.elapsed().as_secs_f64();
+                let tag = match stream {
+                    Stream::Stdout => "out",
+                    Stream::Stderr => "err",
+                };
+                let label = &supervised[id].label;
+                match stream {
+                    Stream::Stdout => println!("[{}] {} +{:.1}s {}", label, tag, elapsed, line),
+                    Stream::Stderr => eprintln!("[{}] {} +{:.1}s {}", label, tag, elapsed, line),
+                }
+                if let Some(f) = &mut log_file {
+                    use std::io::Write;
+                    writeln!(f, "[{}] {} +{:.1}s {}", label, tag, elapsed, line)
+                        .expect("Failed to write to log file");
+                }
+            }
+
             _ => panic!("Main: Unexpected message received"),
 
         } // End of match
@@ -265,9 +633,13 @@ fn main() {
 
 // The command thread starts and manages process that executes the user command given in arguments
 fn command_thread(
+    id: usize,
     command: Vec<String>,
     verbose: u8,
-    thread_to_main_sender: std::sync::mpsc::Sender<Message>,
+    kill_signal: i32,
+    kill_grace: u64,
+    group: bool,
+    thread_to_main_sender: std::sync::mpsc::Sender<(usize, Message)>,
 ) {
     // Macro must be again here where variable verbose is in the local scope
     // Thread reports only debug messages
@@ -285,9 +657,12 @@ fn command_thread(
     }
     // Thread creates a channel to receive messages from the main thread
     let (main_to_thread_sender, main_to_thread_receiver) = std::sync::mpsc::channel::<Message>();
+    // A clone kept so each child's reaper thread can notify this thread of the
+    // child's exit over the same channel main uses, without a second receiver.
+    let reaper_to_thread_sender = main_to_thread_sender.clone();
     // Thread sends its receiving endpoint in the Endpoint message
     thread_to_main_sender
-        .send(Message::Endpoint(main_to_thread_sender))
+        .send((id, Message::Endpoint(main_to_thread_sender)))
         .expect("Failed to send Endpoint message");
     debug!("Endpoint message sent");
     let mut command_process: Option<std::process::Child> = None;
@@ -306,51 +681,141 @@ fn command_thread(
                 if let Some(_) = &mut command_process {
                     panic!("Tried to start a new command while another one was running");
                 }
-                let p = std::process::Command::new(&command[0])
-                    .args(&command[1..])
+                let mut cmd = std::process::Command::new(&command[0]);
+                cmd.args(&command[1..]);
+                // Pipe stdout/stderr so rekill can annotate and optionally log the
+                // child's output instead of letting it write straight to the tty.
+                cmd.stdout(std::process::Stdio::piped());
+                cmd.stderr(std::process::Stdio::piped());
+                // In --group mode the child becomes the leader of a fresh process
+                // group so a later kill can signal the whole subtree at once. On
+                // Unix setsid() in a pre_exec hook makes pgid == pid of the child;
+                // on Windows a new process group is requested at creation time.
+                if group {
+                    #[cfg(unix)]
+                    unsafe {
+                        cmd.pre_exec(|| {
+                            if libc::setsid() == -1 {
+                                return Err(std::io::Error::last_os_error());
+                            }
+                            Ok(())
+                        });
+                    }
+                    #[cfg(windows)]
+                    {
+                        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+                        cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+                    }
+                }
+                let mut p = cmd
                     .spawn()
                     .expect(&format!("Failed to spawn command {:?}", &command));
-                let id = p.id();
+                let pid = p.id();
+                // Drain each pipe from its own thread with line-buffered reads so
+                // the supervisor is never blocked on child I/O; every line is sent
+                // back to main as a Message::Output tagged with this command's id.
+                // The threads end at EOF when the child closes the pipe.
+                if let Some(out) = p.stdout.take() {
+                    let s = thread_to_main_sender.clone();
+                    let _ = std::thread::spawn(move || {
+                        use std::io::BufRead;
+                        for line in std::io::BufReader::new(out).lines() {
+                            match line {
+                                Ok(line) => {
+                                    if s.send((
+                                        id,
+                                        Message::Output {
+                                            stream: Stream::Stdout,
+                                            line,
+                                        },
+                                    ))
+                                    .is_err()
+                                    {
+                                        break;
+                                    }
+                                }
+                                Err(_) => break,
+                            }
+                        }
+                    });
+                }
+                if let Some(err) = p.stderr.take() {
+                    let s = thread_to_main_sender.clone();
+                    let _ = std::thread::spawn(move || {
+                        use std::io::BufRead;
+                        for line in std::io::BufReader::new(err).lines() {
+                            match line {
+                                Ok(line) => {
+                                    if s.send((
+                                        id,
+                                        Message::Output {
+                                            stream: Stream::Stderr,
+                                            line,
+                                        },
+                                    ))
+                                    .is_err()
+                                    {
+                                        break;
+                                    }
+                                }
+                                Err(_) => break,
+                            }
+                        }
+                    });
+                }
+                // A reaper thread blocks until this child exits and then notifies
+                // the command thread over its own channel, so the supervisor
+                // learns of the exit the instant it happens instead of polling.
+                // The reaper does not reap the child itself (Unix WNOWAIT /
+                // Windows WaitForSingleObject); the Reaped handler collects the
+                // ExitStatus with try_wait.
+                {
+                    let s = reaper_to_thread_sender.clone();
+                    #[cfg(unix)]
+                    let token = pid;
+                    #[cfg(windows)]
+                    let token = {
+                        use std::os::windows::io::AsRawHandle;
+                        p.as_raw_handle() as usize
+                    };
+                    let _ = std::thread::spawn(move || {
+                        wait_for_exit(token);
+                        let _ = s.send(Message::Reaped);
+                    });
+                }
                 command_process = Some(p);
-                debug!("The process {} started OK", id);
+                debug!("The process {} started OK", pid);
             }
 
-            // Main thread sends PollCommand
-            // to ask to check if the command is still running
-            Message::Poll => {
-                debug!("Poll message received");
+            // A reaper thread reports that a child exited. Collect its status to
+            // reap it and tell main the command finished. try_wait returning None
+            // means the notification is for a previous, already-replaced child (a
+            // kill reaped it directly), so it is safely ignored.
+            Message::Reaped => {
+                debug!("Reaped message received");
                 if let Some(p) = &mut command_process {
                     match p.try_wait() {
                         Ok(Some(status)) => {
-                            // Command finished with status
                             debug!("Command {:?} finished OK", &command);
                             command_process = None;
-                            // Send CommandFinished
                             thread_to_main_sender
-                                .send(Message::Finished)
+                                .send((id, Message::Finished))
                                 .expect("Failed to send command process finished message");
                             debug!("Command process finished with status {:?}", status);
                             debug!("Finished message sent")
                         }
                         Ok(None) => {
-                            // Command still running
-                            debug!("Command still running");
-                            // Thread sends CommandRunning
-                            thread_to_main_sender
-                                .send(Message::Running)
-                                .expect("Failed to send command process finished message");
-                            debug!("Running message sent");
+                            // A late notification from a child we already reaped
+                            // during a kill and replaced; nothing to do.
+                            debug!("Stale reap notification ignored");
                         }
                         Err(e) => {
-                            panic!("Failed to poll command process: {}", e);
+                            panic!("Failed to reap command process: {}", e);
                         }
                     }
                 } else {
-                    // Command process is not running
-                    // This may happen if poll happens between KillCommand and StartCommand
-                    // Or  if poll happens after (or during) KillCommand
-                    // We just ignore this
-                    debug!("Tried to poll a command process while none was running");
+                    // The child was already reaped by a kill before its reaper ran.
+                    debug!("Reap notification with no command running; ignored");
                 }
             }
 
@@ -359,29 +824,176 @@ fn command_thread(
             Message::Kill => {
                 debug!("Kill message received");
                 if let Some(mut p) = command_process {
-                    debug!("Killing process {}", p.id());
-                    p.kill().expect("Failed to kill command process");
-                    // Kill leaves the process as zombie
-                    // Wait receives return value and releases it
-                    match p.wait() {
-                        Ok(status) => debug!("Process exit OK, status {}", status),
-                        Err(_) => panic!("Failed to reap command process"),
+                    let pid = p.id();
+                    // How often to poll try_wait while waiting out the grace period
+                    const ESCALATE_POLL: u64 = 100; // milliseconds
+                    // First ask the process to stop with the configured signal, then
+                    // give it up to --kill-grace seconds to flush buffers and exit on
+                    // its own. Only if it outlives the deadline do we escalate to an
+                    // unconditional SIGKILL, so a well-behaved child is never SIGKILLed.
+                    // In --group mode signal the whole process group (negative pid)
+                    // so grandchildren die too; otherwise just the direct child.
+                    #[cfg(unix)]
+                    let target = if group { -(pid as i32) } else { pid as i32 };
+                    #[cfg(unix)]
+                    {
+                        debug!("Signalling the process with signal {}", kill_signal);
+                        send_signal(target, kill_signal);
+                    }
+                    #[cfg(not(unix))]
+                    {
+                        // No Unix signals on Windows: fall back to an unconditional kill.
+                        let _ = (kill_signal, group);
+                        debug!("Killing process {}", pid);
+                        p.kill().expect("Failed to kill command process");
+                    }
+                    let deadline =
+                        std::time::Instant::now() + std::time::Duration::from_secs(kill_grace);
+                    loop {
+                        match p.try_wait() {
+                            // Kill leaves the process as a zombie until it is reaped;
+                            // try_wait/wait receive the status and release it.
+                            Ok(Some(status)) => {
+                                debug!("Process exit OK, status {}", status);
+                                break;
+                            }
+                            Ok(None) => {
+                                if std::time::Instant::now() >= deadline {
+                                    debug!("Grace period expired, escalating to SIGKILL");
+                                    #[cfg(unix)]
+                                    send_signal(target, libc::SIGKILL);
+                                    #[cfg(not(unix))]
+                                    p.kill().expect("Failed to kill command process");
+                                    match p.wait() {
+                                        Ok(status) => debug!("Process exit OK, status {}", status),
+                                        Err(_) => panic!("Failed to reap command process"),
+                                    }
+                                    break;
+                                }
+                                std::thread::sleep(std::time::Duration::from_millis(
+                                    ESCALATE_POLL,
+                                ));
+                            }
+                            Err(e) => panic!("Failed to poll command process: {}", e),
+                        }
                     }
                     command_process = None;
+                    // Thread sends Killed only when it actually killed a
+                    // live child, so main restarts (or counts the tear-down) for
+                    // this life exactly once.
+                    thread_to_main_sender
+                        .send((id, Message::Killed))
+                        .expect("Failed to send command process finished message");
+                    debug!("Killed message sent")
                 } else {
-                    // We try to kill process that is already dead
-                    // This may happen due to PollCommand (clean case)
-                    // or the process may have died after the poll
-                    debug!("Tried to kill a command while none was running");
+                    // The child was already reaped after its reaper's notification (a natural exit
+                    // raced the timeout): the Finished message already told main,
+                    // so we stay silent here rather than sending a second terminal
+                    // event that would drive a duplicate restart.
+                    debug!("Tried to kill a command while none was running; no Killed sent");
                 }
-                // Thread sends CommandKilled
-                thread_to_main_sender
-                    .send(Message::Killed)
-                    .expect("Failed to send command process finished message");
-                debug!("Killed message sent")
             }
 
             _ => panic!("Unexpected main to thread message"),
         } // End of match
     } // End of loop
 } // End of fn
+
+// The timer thread's countdown state. Running is counting down toward the
+// deadline; Paused was frozen by SIGUSR1 and remembers its remaining time; Fired
+// already reported a Timeout and waits for ResetTimer. Paused and Fired are kept
+// distinct so a stray ResumeTimer (SIGUSR2) after a timeout cannot resurrect a
+// countdown that already fired.
+enum TimerState {
+    Running,
+    Paused,
+    Fired,
+}
+
+// The timer thread owns the timeout countdown so the restart path can reset it
+// and SIGUSR1/SIGUSR2 can pause/resume it. It blocks until either the deadline
+// elapses (then it reports Message::Timeout to main) or a control message
+// arrives. After firing it parks itself until the next ResetTimer so it does not
+// fire repeatedly while main kills and restarts the command.
+fn timer_thread(
+    id: usize,
+    time: u64,
+    verbose: u8,
+    timer_receiver: std::sync::mpsc::Receiver<Message>,
+    timer_to_main_sender: std::sync::mpsc::Sender<(usize, Message)>,
+) {
+    macro_rules! debug {
+        ($message:expr) => {
+            if verbose >= 2 {
+                eprintln!(concat!("Timer thread: ", $message));
+            }
+        };
+        ($message:expr, $value:expr) => {
+            if verbose >= 2 {
+                eprintln!(concat!("Timer thread: ", $message), $value);
+            }
+        };
+    }
+    debug!("Timer thread started");
+    let duration = std::time::Duration::from_secs(time);
+    let mut deadline = std::time::Instant::now() + duration;
+    // Remaining duration, only meaningful while paused.
+    let mut remaining = duration;
+    let mut state = TimerState::Running;
+    loop {
+        let received = match state {
+            // Counting down: wake either at the deadline or on a control message.
+            TimerState::Running => {
+                let wait = deadline.saturating_duration_since(std::time::Instant::now());
+                timer_receiver.recv_timeout(wait)
+            }
+            // Paused or already fired: wait for a control message without a
+            // deadline so the countdown stays frozen.
+            TimerState::Paused | TimerState::Fired => timer_receiver
+                .recv()
+                .map_err(|_| std::sync::mpsc::RecvTimeoutError::Disconnected),
+        };
+        match received {
+            // Restart the countdown from zero and make sure we are running.
+            Ok(Message::ResetTimer) => {
+                debug!("ResetTimer received");
+                deadline = std::time::Instant::now() + duration;
+                remaining = duration;
+                state = TimerState::Running;
+            }
+            // Freeze: remember how much time was left so resume can restore it.
+            // Only a running countdown can be paused.
+            Ok(Message::PauseTimer) => {
+                debug!("PauseTimer received");
+                if let TimerState::Running = state {
+                    remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                    state = TimerState::Paused;
+                }
+            }
+            // Recompute the deadline from the remembered remaining duration. Only
+            // a genuinely paused timer resumes; a fired one stays fired until the
+            // next ResetTimer so it is not spuriously re-armed.
+            Ok(Message::ResumeTimer) => {
+                debug!("ResumeTimer received");
+                if let TimerState::Paused = state {
+                    deadline = std::time::Instant::now() + remaining;
+                    state = TimerState::Running;
+                }
+            }
+            // Deadline reached: report the timeout and park until the next reset.
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                debug!("Countdown elapsed, sending Timeout");
+                timer_to_main_sender
+                    .send((id, Message::Timeout))
+                    .expect("Failed to send timeout message");
+                state = TimerState::Fired;
+            }
+            // Main is gone; the thread can exit (main is exiting too).
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                debug!("Timer channel disconnected, exiting");
+                return;
+            }
+            _ => panic!("Timer thread: unexpected message"),
+        }
+    }
+}